@@ -0,0 +1,152 @@
+//! Collecting the set of anchors (heading slugs and explicit HTML ids) that a
+//! Markdown file makes available as link fragments.
+
+use std::{
+    collections::HashSet,
+    fs,
+    path::Path,
+};
+
+use comrak::{
+    nodes::{AstNode, NodeValue},
+    parse_document, Arena, ComrakExtensionOptions, ComrakOptions,
+};
+use regex::Regex;
+
+/// Parse `path` as Markdown and return every anchor it exposes: one GitHub-style
+/// slug per heading, plus any explicit `id="..."`/`name="..."` found in inline HTML.
+/// Returns an empty set if the file cannot be read.
+pub fn collect_anchors(path: &Path) -> HashSet<String> {
+    let file_contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(_) => return HashSet::new(),
+    };
+
+    let arena = Arena::new();
+    let opts = ComrakOptions {
+        extension: ComrakExtensionOptions {
+            table: true,
+            autolink: true,
+            ..ComrakExtensionOptions::default()
+        },
+        ..ComrakOptions::default()
+    };
+    let root = parse_document(&arena, &file_contents, &opts);
+
+    let mut anchors = HashSet::new();
+    let mut slugs_seen: HashSet<String> = HashSet::new();
+    collect_node_anchors(root, &mut anchors, &mut slugs_seen);
+    anchors
+}
+
+fn collect_node_anchors<'a>(
+    node: &'a AstNode<'a>,
+    anchors: &mut HashSet<String>,
+    slugs_seen: &mut HashSet<String>,
+) {
+    match node.data.borrow().value {
+        NodeValue::Heading(_) => {
+            let text = heading_text(node);
+            anchors.insert(dedupe_slug(&slugify(&text), slugs_seen));
+        }
+        NodeValue::HtmlInline(ref html) => {
+            for id in extract_html_ids(&String::from_utf8_lossy(html)) {
+                anchors.insert(id);
+            }
+        }
+        NodeValue::HtmlBlock(ref html) => {
+            for id in extract_html_ids(&String::from_utf8_lossy(&html.literal)) {
+                anchors.insert(id);
+            }
+        }
+        _ => {}
+    }
+
+    for child in node.children() {
+        collect_node_anchors(child, anchors, slugs_seen);
+    }
+}
+
+/// Concatenate the text content of a heading node's descendants.
+fn heading_text<'a>(node: &'a AstNode<'a>) -> String {
+    let mut text = String::new();
+    collect_text(node, &mut text);
+    text
+}
+
+fn collect_text<'a>(node: &'a AstNode<'a>, output: &mut String) {
+    if let NodeValue::Text(ref t) = node.data.borrow().value {
+        output.push_str(&String::from_utf8_lossy(t));
+    }
+    for child in node.children() {
+        collect_text(child, output);
+    }
+}
+
+/// Turn heading text into a GitHub-style slug: lowercase, strip anything that
+/// isn't alphanumeric/space/hyphen, then replace spaces with hyphens.
+pub fn slugify(text: &str) -> String {
+    let stripped: String = text
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == ' ' || *c == '-')
+        .collect();
+    stripped.replace(' ', "-")
+}
+
+/// Disambiguate a slug against those already seen by appending `-1`, `-2`, etc.
+fn dedupe_slug(slug: &str, slugs_seen: &mut HashSet<String>) -> String {
+    if slugs_seen.insert(slug.to_string()) {
+        return slug.to_string();
+    }
+    let mut n = 1;
+    loop {
+        let candidate = format!("{}-{}", slug, n);
+        if slugs_seen.insert(candidate.clone()) {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+/// Pull out `id="..."`/`name="..."` attribute values from a chunk of raw HTML.
+fn extract_html_ids(html: &str) -> Vec<String> {
+    let re = Regex::new(r#"(?:id|name)\s*=\s*"([^"]+)""#).unwrap();
+    re.captures_iter(html)
+        .map(|cap| cap[1].to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slugify_lowercases_and_hyphenates_spaces() {
+        assert_eq!(slugify("Section Two"), "section-two");
+    }
+
+    #[test]
+    fn slugify_strips_punctuation() {
+        assert_eq!(slugify("What's New?!"), "whats-new");
+    }
+
+    #[test]
+    fn slugify_keeps_existing_hyphens() {
+        assert_eq!(slugify("already-slugified"), "already-slugified");
+    }
+
+    #[test]
+    fn dedupe_slug_returns_first_occurrence_unchanged() {
+        let mut seen = HashSet::new();
+        assert_eq!(dedupe_slug("setup", &mut seen), "setup");
+    }
+
+    #[test]
+    fn dedupe_slug_numbers_repeated_slugs() {
+        let mut seen = HashSet::new();
+        assert_eq!(dedupe_slug("setup", &mut seen), "setup");
+        assert_eq!(dedupe_slug("setup", &mut seen), "setup-1");
+        assert_eq!(dedupe_slug("setup", &mut seen), "setup-2");
+    }
+}
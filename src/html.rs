@@ -0,0 +1,34 @@
+//! Checking links in rendered/authored HTML files (e.g. mdBook or rustdoc
+//! output), where links live in `href`/`src` attributes rather than comrak's
+//! Markdown AST.
+
+use std::{collections::HashSet, fs, path::Path};
+
+use regex::Regex;
+
+/// Whether `file` looks like an HTML file, by extension.
+pub fn is_html(file: &Path) -> bool {
+    match file.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => matches!(ext.to_lowercase().as_str(), "html" | "htm"),
+        None => false,
+    }
+}
+
+/// Pull every `href="..."`/`src="..."` attribute value out of an HTML document.
+pub fn extract_links(html: &str) -> Vec<String> {
+    let re = Regex::new(r#"(?:href|src)\s*=\s*"([^"]*)""#).unwrap();
+    re.captures_iter(html).map(|cap| cap[1].to_string()).collect()
+}
+
+/// Collect every `id="..."` anchor declared in the HTML file at `path`.
+/// Returns an empty set if the file cannot be read.
+pub fn collect_anchors(path: &Path) -> HashSet<String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(_) => return HashSet::new(),
+    };
+    let re = Regex::new(r#"id\s*=\s*"([^"]+)""#).unwrap();
+    re.captures_iter(&contents)
+        .map(|cap| cap[1].to_string())
+        .collect()
+}
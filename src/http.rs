@@ -0,0 +1,61 @@
+//! Checking remote `http`/`https` links for reachability.
+
+use std::time::Duration;
+
+use reqwest::blocking::{Client, Response};
+use reqwest::StatusCode;
+
+/// The outcome of checking a single remote URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    /// The URL returned a successful (2xx) response.
+    Ok,
+    /// The URL responded, but with a non-2xx status code.
+    NotFound(u16),
+    /// The request could not be completed, e.g. a DNS failure or connection refusal.
+    Unreachable(String),
+    /// The request did not complete before the timeout elapsed.
+    Timeout,
+}
+
+impl Status {
+    /// Whether this status should be reported as a broken link.
+    pub fn is_broken(&self) -> bool {
+        !matches!(self, Status::Ok)
+    }
+}
+
+/// Build the HTTP client used for remote link checks.
+pub fn build_client() -> Client {
+    Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .expect("failed to build HTTP client")
+}
+
+/// Check a single URL, issuing a HEAD request first and falling back to GET
+/// when the server doesn't support HEAD.
+pub fn check_url(client: &Client, url: &str) -> Status {
+    match client.head(url).send() {
+        Ok(resp) if resp.status() == StatusCode::METHOD_NOT_ALLOWED => check_with_get(client, url),
+        Ok(resp) => status_from_response(resp),
+        Err(e) if e.is_timeout() => Status::Timeout,
+        Err(_) => check_with_get(client, url),
+    }
+}
+
+fn check_with_get(client: &Client, url: &str) -> Status {
+    match client.get(url).send() {
+        Ok(resp) => status_from_response(resp),
+        Err(e) if e.is_timeout() => Status::Timeout,
+        Err(e) => Status::Unreachable(e.to_string()),
+    }
+}
+
+fn status_from_response(resp: Response) -> Status {
+    if resp.status().is_success() {
+        Status::Ok
+    } else {
+        Status::NotFound(resp.status().as_u16())
+    }
+}
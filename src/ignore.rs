@@ -0,0 +1,90 @@
+//! Loading an ignore-file of known-broken or intentionally-dynamic links, so
+//! a repo can adopt the validator in CI without first fixing every legacy link.
+
+use std::{fs, io, path::Path};
+
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Default)]
+struct IgnoreFile {
+    #[serde(default)]
+    ignore: Vec<IgnoreRule>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IgnoreRule {
+    /// Glob matched against the scanned source file's path.
+    source: String,
+    /// Globs matched against the broken link text; any one match suppresses it.
+    links: Vec<String>,
+}
+
+/// A loaded set of `(source glob, link globs)` exceptions, consulted before a
+/// broken link is reported.
+#[derive(Debug, Default)]
+pub struct IgnoreList {
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreList {
+    /// Load an ignore-file in TOML format from `path`.
+    pub fn load(path: &Path) -> io::Result<IgnoreList> {
+        let contents = fs::read_to_string(path)?;
+        let parsed: IgnoreFile = toml::from_str(&contents).unwrap_or_else(|e| {
+            eprintln!("Failed to parse ignore-file `{}`: {}", path.display(), e);
+            IgnoreFile::default()
+        });
+        Ok(IgnoreList {
+            rules: parsed.ignore,
+        })
+    }
+
+    /// Whether `link`, as found in `source`, should be suppressed.
+    pub fn is_ignored(&self, source: &Path, link: &str) -> bool {
+        let source_str = source.to_string_lossy();
+        self.rules.iter().any(|rule| {
+            glob_match(&rule.source, &source_str) && rule.links.iter().any(|pat| glob_match(pat, link))
+        })
+    }
+}
+
+/// Match `text` against `pattern`, where `*` matches any run of characters (including none).
+fn glob_match(pattern: &str, text: &str) -> bool {
+    fn helper(p: &[u8], t: &[u8]) -> bool {
+        if p.is_empty() {
+            return t.is_empty();
+        }
+        if p[0] == b'*' {
+            return helper(&p[1..], t) || (!t.is_empty() && helper(p, &t[1..]));
+        }
+        if t.is_empty() {
+            return false;
+        }
+        p[0] == t[0] && helper(&p[1..], &t[1..])
+    }
+    helper(pattern.as_bytes(), text.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_literal_text() {
+        assert!(glob_match("docs/readme.md", "docs/readme.md"));
+        assert!(!glob_match("docs/readme.md", "docs/other.md"));
+    }
+
+    #[test]
+    fn star_matches_any_run_including_none() {
+        assert!(glob_match("docs/*.md", "docs/readme.md"));
+        assert!(glob_match("docs/*.md", "docs/.md"));
+        assert!(!glob_match("docs/*.md", "src/readme.md"));
+    }
+
+    #[test]
+    fn leading_and_trailing_star_match_substrings() {
+        assert!(glob_match("*other-note*", "[[other-note]]"));
+        assert!(!glob_match("*other-note*", "[[different-note]]"));
+    }
+}
@@ -1,7 +1,9 @@
 use std::{
+    collections::{HashMap, HashSet},
     fs::File,
     io::{self, Read, Write},
     path::{Path, PathBuf},
+    sync::Mutex,
 };
 
 use clap::{app_from_crate, crate_authors, crate_description, crate_name, crate_version, Arg};
@@ -10,10 +12,40 @@ use comrak::{
     parse_document, Arena, ComrakExtensionOptions, ComrakOptions,
 };
 use percent_encoding::percent_decode;
+use rayon::{prelude::*, ThreadPoolBuilder};
+use reqwest::blocking::Client;
 use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
 use url::Url;
 use walkdir::WalkDir;
 
+mod anchors;
+mod html;
+mod http;
+mod ignore;
+mod report;
+mod wikilinks;
+
+use http::Status;
+use ignore::IgnoreList;
+use report::{BrokenLink, FileReport, Kind};
+
+/// Anchor sets parsed per target file, shared across threads when scanning a directory.
+type AnchorCache = Mutex<HashMap<PathBuf, HashSet<String>>>;
+/// Remote URL check results, shared across threads when scanning a directory.
+type HttpCache = Mutex<HashMap<String, Status>>;
+
+/// Everything a single [`scan_file`] call needs, bundled up so it can be
+/// shared by reference across scans instead of threaded as positional args.
+struct ScanContext<'a> {
+    client: &'a Client,
+    http_cache: &'a HttpCache,
+    anchor_cache: &'a AnchorCache,
+    note_index: &'a HashMap<String, PathBuf>,
+    ignore_list: &'a IgnoreList,
+    check_http: bool,
+    check_wikilinks: bool,
+}
+
 fn main() {
     let matches = app_from_crate!()
         .arg(Arg::with_name("src")
@@ -21,20 +53,103 @@ fn main() {
             .required(true)
             .help("Source file or directory to parse. If a directory, validates every Markdown file found within it.")
         )
+        .arg(Arg::with_name("check-http")
+            .long("check-http")
+            .help("Also validate remote http(s) links by requesting them and reporting non-2xx or unreachable URLs.")
+        )
+        .arg(Arg::with_name("wikilinks")
+            .long("wikilinks")
+            .help("Also validate Obsidian-style [[wikilinks]], resolving targets by note name across the scanned directory.")
+        )
+        .arg(Arg::with_name("jobs")
+            .long("jobs")
+            .short("j")
+            .takes_value(true)
+            .help("Number of threads to use when scanning a directory (default: number of CPUs).")
+        )
+        .arg(Arg::with_name("format")
+            .long("format")
+            .takes_value(true)
+            .possible_values(&["text", "json"])
+            .default_value("text")
+            .help("Output format. `json` emits a machine-readable report instead of coloured text.")
+        )
+        .arg(Arg::with_name("ignore-file")
+            .long("ignore-file")
+            .takes_value(true)
+            .help("A TOML file of [[ignore]] rules (source glob + link globs) for links that should never be reported as broken.")
+        )
+        .arg(Arg::with_name("include-html")
+            .long("include-html")
+            .help("Also validate href/src/id attributes in .html/.htm files, e.g. the output of mdBook or rustdoc.")
+        )
         .get_matches();
 
+    if let Some(jobs) = matches.value_of("jobs") {
+        match jobs.parse::<usize>() {
+            Ok(n) => {
+                let _ = ThreadPoolBuilder::new().num_threads(n).build_global();
+            }
+            Err(_) => eprintln!("`{}` is not a valid number of jobs. Using the default.", jobs),
+        }
+    }
+
     // check that the input source is a file or directory that exists
     let src = PathBuf::from(matches.value_of("src").unwrap());
+    let check_http = matches.is_present("check-http");
+    let check_wikilinks = matches.is_present("wikilinks");
+    let include_html = matches.is_present("include-html");
+    let json_format = matches.value_of("format") == Some("json");
+    let client = http::build_client();
+    let http_cache: HttpCache = Mutex::new(HashMap::new());
+    let anchor_cache: AnchorCache = Mutex::new(HashMap::new());
+    let note_index = if check_wikilinks {
+        let vault_root = if src.is_dir() {
+            src.clone()
+        } else {
+            src.parent().map(Path::to_path_buf).unwrap_or_default()
+        };
+        wikilinks::build_note_index(&vault_root)
+    } else {
+        HashMap::new()
+    };
+    let ignore_list = match matches.value_of("ignore-file") {
+        Some(path) => match IgnoreList::load(Path::new(path)) {
+            Ok(list) => list,
+            Err(e) => {
+                eprintln!("Could not read ignore-file `{}`: {}", path, e);
+                IgnoreList::default()
+            }
+        },
+        None => IgnoreList::default(),
+    };
+
     if !src.exists() {
         eprintln!("`{}` not found. Skipping.", src.display());
+        std::process::exit(1);
     }
+
+    let ctx = ScanContext {
+        client: &client,
+        http_cache: &http_cache,
+        anchor_cache: &anchor_cache,
+        note_index: &note_index,
+        ignore_list: &ignore_list,
+        check_http,
+        check_wikilinks,
+    };
+
+    let mut reports: Vec<FileReport> = Vec::new();
+
     if src.is_file() {
-        if is_markdown(src.as_path()) {
-            let missing_links = get_missing_links(src.as_path());
-            if missing_links.len() > 0 {
-                eprintln!("The following linked files cannot be found:");
+        if is_scannable(src.as_path(), include_html) {
+            let broken = scan_file(src.as_path(), &ctx);
+            if !broken.is_empty() {
+                reports.push(FileReport {
+                    source: src.clone(),
+                    broken,
+                });
             }
-            print_missing(missing_links, src.as_path(), false);
         } else {
             eprintln!(
                 "`{}` does not appear to me a Markdown file. Skipping.",
@@ -42,29 +157,74 @@ fn main() {
             );
         }
     } else if src.is_dir() {
-        let mut any_missing = false;
-        for entry in WalkDir::new(&src)
+        // collect the entries up front so the scan itself can run in parallel
+        let entries: Vec<PathBuf> = WalkDir::new(&src)
             .into_iter()
             .filter_map(|e| e.ok())
-            .filter(|e| is_markdown(e.path()))
-        {
-            let missing_links = get_missing_links(entry.path());
-            if (missing_links.len() > 0) && !any_missing {
-                any_missing = true;
-                eprintln!("The following linked files cannot be found:");
-            }
-            print_missing(missing_links, entry.path(), true);
-        }
+            .filter(|e| is_scannable(e.path(), include_html))
+            .map(|e| e.path().to_path_buf())
+            .collect();
+
+        reports = entries
+            .par_iter()
+            .map(|path| {
+                let broken = scan_file(path, &ctx);
+                FileReport {
+                    source: path.clone(),
+                    broken,
+                }
+            })
+            .collect();
+
+        // merge deterministically, so output order stays stable across runs
+        reports.retain(|r| !r.broken.is_empty());
+        reports.sort_by(|a, b| a.source.cmp(&b.source));
     } else {
         eprintln!(
             "`{}` is neither a file nor a directory. Skipping.",
             src.display()
         );
+        std::process::exit(1);
+    }
+
+    let any_broken = !reports.is_empty();
+
+    if json_format {
+        println!("{}", serde_json::to_string(&reports).unwrap());
+    } else {
+        if any_broken {
+            eprintln!("The following linked files cannot be found:");
+        }
+        let print_filename = src.is_dir();
+        for report in &reports {
+            print_broken(&report.broken, &report.source, print_filename);
+        }
+    }
+
+    if any_broken {
+        std::process::exit(1);
     }
 }
 
-/// Convert the markdown document to a string
-fn read_markdown(path: &Path) -> io::Result<String> {
+/// Run every enabled checker against a single Markdown file, returning its
+/// combined list of broken links.
+fn scan_file(file: &Path, ctx: &ScanContext) -> Vec<BrokenLink> {
+    let mut broken = get_missing_links(file, ctx.anchor_cache);
+
+    if ctx.check_http {
+        broken.extend(get_broken_http_links(file, ctx.client, ctx.http_cache));
+    }
+
+    if ctx.check_wikilinks {
+        broken.extend(get_missing_wikilinks(file, ctx.note_index, ctx.anchor_cache));
+    }
+
+    broken.retain(|b| !ctx.ignore_list.is_ignored(file, &b.link));
+    broken
+}
+
+/// Read a file's contents into a string
+fn read_file(path: &Path) -> io::Result<String> {
     let mut file = File::open(&path)?;
     let mut file_contents = String::new();
     file.read_to_string(&mut file_contents)?;
@@ -87,8 +247,15 @@ fn extract_links<'a>(node: &'a AstNode<'a>, output: &mut Vec<String>) {
     }
 }
 
-fn get_missing_links(file: &Path) -> Vec<PathBuf> {
-    let file_contents = read_markdown(file).unwrap();
+/// Pull every linked target out of `file`, dispatching on whether it's
+/// Markdown or HTML.
+fn extract_link_strings(file: &Path) -> Vec<String> {
+    let file_contents = read_file(file).unwrap();
+
+    if html::is_html(file) {
+        return html::extract_links(&file_contents);
+    }
+
     let arena = Arena::new();
     let opts = ComrakOptions {
         extension: ComrakExtensionOptions {
@@ -100,14 +267,48 @@ fn get_missing_links(file: &Path) -> Vec<PathBuf> {
     };
     let root = parse_document(&arena, &file_contents, &opts);
 
-    // keep track of all the links in the file
     let mut links: Vec<String> = Vec::new();
-    let mut file_links: Vec<PathBuf> = Vec::new();
-
-    // iterate through all the nodes to collect links
     for node in root.children() {
         extract_links(node, &mut links);
     }
+    links
+}
+
+/// Look up `key` in `cache`, computing it with `f` and storing the result if
+/// it's missing. `f` runs outside the lock, so a slow computation (a network
+/// request, a file parse) for one key doesn't block other threads working on
+/// different keys.
+fn cached_or_compute<K, V, F>(cache: &Mutex<HashMap<K, V>>, key: &K, f: F) -> V
+where
+    K: std::hash::Hash + Eq + Clone,
+    V: Clone,
+    F: FnOnce() -> V,
+{
+    if let Some(v) = cache.lock().unwrap().get(key) {
+        return v.clone();
+    }
+    let v = f();
+    cache.lock().unwrap().entry(key.clone()).or_insert_with(|| v.clone());
+    v
+}
+
+/// Collect the anchors a link target exposes, dispatching on whether it's a
+/// Markdown heading slug or an HTML `id` attribute.
+fn collect_target_anchors(path: &Path) -> HashSet<String> {
+    if html::is_html(path) {
+        html::collect_anchors(path)
+    } else {
+        anchors::collect_anchors(path)
+    }
+}
+
+fn get_missing_links(file: &Path, anchor_cache: &AnchorCache) -> Vec<BrokenLink> {
+    // keep track of all the links in the file
+    let links = extract_link_strings(file);
+    // each local link, split into the file part and an optional `#fragment`
+    let mut file_links: Vec<(PathBuf, Option<String>)> = Vec::new();
+    // same-document anchor-only links, e.g. `[Back to top](#title)`
+    let mut same_file_fragments: Vec<String> = Vec::new();
 
     // for each link, determine if it's a URL or a local file
     for l in &links {
@@ -116,8 +317,14 @@ fn get_missing_links(file: &Path) -> Vec<PathBuf> {
             // if it's not a URL, decode the percentage-encoded characters
             match percent_decode(l.as_bytes()).decode_utf8() {
                 Ok(decoded) => {
-                    let p = PathBuf::from(decoded.to_string());
-                    file_links.push(p);
+                    let (path_part, fragment) = split_fragment(&decoded);
+                    if path_part.is_empty() {
+                        if let Some(frag) = fragment {
+                            same_file_fragments.push(frag);
+                        }
+                    } else {
+                        file_links.push((PathBuf::from(path_part), fragment));
+                    }
                 }
                 Err(e) => {
                     eprintln!("Error decoding the following path: {}", l);
@@ -133,7 +340,7 @@ fn get_missing_links(file: &Path) -> Vec<PathBuf> {
         Some(dir) => dir.to_path_buf(),
         None => PathBuf::new(),
     };
-    for l in file_links.iter_mut() {
+    for (l, _) in file_links.iter_mut() {
         if l.is_relative() {
             // can guarantee the unwrap because of the file name validation from before
             let new_file = base_dir.join(l.as_path());
@@ -141,17 +348,146 @@ fn get_missing_links(file: &Path) -> Vec<PathBuf> {
         }
     }
 
-    // check that each file link exists
-    let mut missing_links: Vec<PathBuf> = Vec::new();
-    for l in &file_links {
+    // check that each file link exists, and that its fragment (if any) names an
+    // anchor that actually exists within the target file
+    let mut missing_links: Vec<BrokenLink> = Vec::new();
+    for (l, fragment) in &file_links {
         if !l.exists() {
-            missing_links.push(l.clone());
+            missing_links.push(BrokenLink {
+                link: with_fragment(l, fragment).display().to_string(),
+                resolved: None,
+                kind: Kind::MissingFile,
+            });
+            continue;
+        }
+        if let Some(frag) = fragment {
+            let found = cached_or_compute(anchor_cache, l, || collect_target_anchors(l)).contains(frag);
+            if !found {
+                missing_links.push(BrokenLink {
+                    link: with_fragment(l, fragment).display().to_string(),
+                    resolved: Some(l.clone()),
+                    kind: Kind::MissingAnchor,
+                });
+            }
+        }
+    }
+
+    // anchor-only links (e.g. `[Back to top](#title)`) refer to this file itself,
+    // not to the directory it lives in
+    for frag in &same_file_fragments {
+        let this_file = file.to_path_buf();
+        let found = cached_or_compute(anchor_cache, &this_file, || collect_target_anchors(file)).contains(frag);
+        if !found {
+            missing_links.push(BrokenLink {
+                link: format!("#{}", frag),
+                resolved: Some(file.to_path_buf()),
+                kind: Kind::MissingAnchor,
+            });
         }
     }
 
     missing_links
 }
 
+/// Split a decoded link on its first `#`, separating the file path from the
+/// fragment (if any) so the two can be validated independently.
+fn split_fragment(link: &str) -> (String, Option<String>) {
+    match link.split_once('#') {
+        Some((path, fragment)) => (path.to_string(), Some(fragment.to_string())),
+        None => (link.to_string(), None),
+    }
+}
+
+/// Re-attach a fragment to a resolved path, purely for reporting purposes.
+fn with_fragment(path: &Path, fragment: &Option<String>) -> PathBuf {
+    match fragment {
+        Some(frag) => PathBuf::from(format!("{}#{}", path.display(), frag)),
+        None => path.to_path_buf(),
+    }
+}
+
+/// Collect every remote `http`/`https` URL linked from a Markdown file and, using
+/// `http_cache` to avoid re-requesting URLs already seen elsewhere in the scan,
+/// check each one and return those that are broken.
+fn get_broken_http_links(
+    file: &Path,
+    client: &Client,
+    http_cache: &HttpCache,
+) -> Vec<BrokenLink> {
+    let links = extract_link_strings(file);
+
+    let mut broken: Vec<BrokenLink> = Vec::new();
+    for l in &links {
+        if Url::parse(l).is_err() {
+            continue;
+        }
+        let status = cached_or_compute(http_cache, l, || http::check_url(client, l));
+        if status.is_broken() {
+            broken.push(BrokenLink {
+                link: format!("{} ({})", l, describe_status(&status)),
+                resolved: None,
+                kind: Kind::HttpError,
+            });
+        }
+    }
+
+    broken
+}
+
+/// Find `[[wikilinks]]` in `file` whose target note can't be found in
+/// `note_index`, or whose `#block`/heading reference doesn't resolve to an
+/// anchor in the target note.
+fn get_missing_wikilinks(
+    file: &Path,
+    note_index: &HashMap<String, PathBuf>,
+    anchor_cache: &AnchorCache,
+) -> Vec<BrokenLink> {
+    let file_contents = read_file(file).unwrap();
+    let arena = Arena::new();
+    let opts = ComrakOptions {
+        extension: ComrakExtensionOptions {
+            table: true,
+            autolink: true,
+            ..ComrakExtensionOptions::default()
+        },
+        ..ComrakOptions::default()
+    };
+    let root = parse_document(&arena, &file_contents, &opts);
+    let mut missing = Vec::new();
+
+    for link in wikilinks::extract_wikilinks(root) {
+        match note_index.get(&link.file) {
+            None => missing.push(BrokenLink {
+                link: format!("[[{}]]", link.file),
+                resolved: None,
+                kind: Kind::MissingFile,
+            }),
+            Some(target) => {
+                if let Some(block) = &link.block {
+                    let slug = anchors::slugify(block);
+                    let found =
+                        cached_or_compute(anchor_cache, target, || collect_target_anchors(target)).contains(&slug);
+                    if !found {
+                        missing.push(BrokenLink {
+                            link: format!("[[{}#{}]]", link.file, block),
+                            resolved: Some(target.clone()),
+                            kind: Kind::MissingAnchor,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    missing
+}
+
+/// Whether `file` should be scanned at all: always true for Markdown, and
+/// true for HTML only when `include_html` is set.
+fn is_scannable(file: &Path, include_html: bool) -> bool {
+    is_markdown(file) || (include_html && html::is_html(file))
+}
+
 /// Check if the file appears to be a Markdown text file
 fn is_markdown(file: &Path) -> bool {
     match file.extension() {
@@ -166,18 +502,30 @@ fn is_markdown(file: &Path) -> bool {
     }
 }
 
-/// Print the missing links associated with the source file
-fn print_missing(missing: Vec<PathBuf>, file: &Path, print_filename: bool) {
-    if print_filename {
-        for m in missing {
+/// Print the broken links found in a source file, colouring local failures
+/// (missing files/anchors) differently from remote HTTP failures so users can
+/// tell the two classes apart at a glance.
+fn print_broken(broken: &[BrokenLink], file: &Path, print_filename: bool) {
+    for b in broken {
+        let colour = match b.kind {
+            Kind::MissingFile | Kind::MissingAnchor => Color::White,
+            Kind::HttpError => Color::Red,
+        };
+        if print_filename {
             eprintln!("");
             writeln_colour(file.to_str().unwrap(), Color::Magenta);
-            writeln_colour(m.to_str().unwrap(), Color::White);
-        }
-    } else {
-        for m in missing {
-            writeln_colour(m.to_str().unwrap(), Color::White);
         }
+        writeln_colour(&b.link, colour);
+    }
+}
+
+/// Render an HTTP [`Status`] as a short human-readable label.
+fn describe_status(status: &Status) -> String {
+    match status {
+        Status::Ok => String::from("ok"),
+        Status::NotFound(code) => format!("status {}", code),
+        Status::Unreachable(reason) => format!("could not connect: {}", reason),
+        Status::Timeout => String::from("timed out"),
     }
 }
 
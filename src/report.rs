@@ -0,0 +1,33 @@
+//! The structured description of a broken link, shared by every checker
+//! (local files, anchors, remote URLs, wikilinks) so results can be printed
+//! as coloured text or serialized as JSON through the same path.
+
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+/// What kind of problem was found with a link.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Kind {
+    MissingFile,
+    MissingAnchor,
+    HttpError,
+}
+
+/// A single broken link found while scanning a source file.
+#[derive(Debug, Clone, Serialize)]
+pub struct BrokenLink {
+    /// The link as written (or as close to it as we keep around) in the source file.
+    pub link: String,
+    /// The local path the link resolved to, if any.
+    pub resolved: Option<PathBuf>,
+    pub kind: Kind,
+}
+
+/// The broken links found in a single scanned source file.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileReport {
+    pub source: PathBuf,
+    pub broken: Vec<BrokenLink>,
+}
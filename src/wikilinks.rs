@@ -0,0 +1,150 @@
+//! Parsing Obsidian-style `[[wikilinks]]`, which comrak has no concept of and
+//! so never surfaces as `NodeValue::Link`.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use comrak::nodes::{AstNode, NodeValue};
+use regex::Regex;
+use walkdir::WalkDir;
+
+/// A single `[[wikilink]]`, split into its note name, optional `#block`/heading
+/// reference, and optional `|alias` label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WikiLink {
+    pub file: String,
+    pub block: Option<String>,
+    pub label: Option<String>,
+}
+
+/// Find every `[[...]]` span in a parsed Markdown document and parse its
+/// contents into a [`WikiLink`]. Only plain text is scanned, so a code span or
+/// fenced/indented code block containing literal `[[...]]` (e.g. C++'s
+/// `[[nodiscard]]`) is never mistaken for a wikilink.
+pub fn extract_wikilinks<'a>(root: &'a AstNode<'a>) -> Vec<WikiLink> {
+    let mut text = String::new();
+    collect_plain_text(root, &mut text);
+    extract_wikilinks_from_text(&text)
+}
+
+/// Concatenate the content of every `Text` node, skipping `Code`/`CodeBlock`
+/// nodes entirely so their literal contents are never scanned.
+fn collect_plain_text<'a>(node: &'a AstNode<'a>, output: &mut String) {
+    match node.data.borrow().value {
+        NodeValue::Text(ref t) => output.push_str(&String::from_utf8_lossy(t)),
+        NodeValue::Code(_) | NodeValue::CodeBlock(_) => return,
+        _ => {}
+    }
+    for child in node.children() {
+        collect_plain_text(child, output);
+    }
+}
+
+fn extract_wikilinks_from_text(text: &str) -> Vec<WikiLink> {
+    let span_re = Regex::new(r"\[\[([^\]]+)\]\]").unwrap();
+    let inner_re = Regex::new(r"^(?P<file>[^#|]+)(#(?P<block>.+?))?(\|(?P<label>.+?))?$").unwrap();
+
+    span_re
+        .captures_iter(text)
+        .filter_map(|span| {
+            let inner = &span[1];
+            inner_re.captures(inner).map(|cap| WikiLink {
+                file: cap["file"].trim().to_string(),
+                block: cap.name("block").map(|m| m.as_str().trim().to_string()),
+                label: cap.name("label").map(|m| m.as_str().trim().to_string()),
+            })
+        })
+        .collect()
+}
+
+/// Build an index of note name (file stem, as Obsidian resolves them) to path,
+/// covering every Markdown file found under `root`.
+pub fn build_note_index(root: &Path) -> HashMap<String, PathBuf> {
+    let mut index = HashMap::new();
+    for entry in WalkDir::new(root)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| is_markdown(e.path()))
+    {
+        if let Some(stem) = entry.path().file_stem().and_then(|s| s.to_str()) {
+            index.insert(stem.to_string(), entry.path().to_path_buf());
+        }
+    }
+    index
+}
+
+/// Whether `file` looks like a Markdown file, by extension.
+fn is_markdown(file: &Path) -> bool {
+    match file.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => matches!(ext.to_lowercase().as_str(), "md" | "markdown"),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use comrak::{parse_document, Arena, ComrakExtensionOptions, ComrakOptions};
+
+    fn parse(markdown: &str) -> Vec<WikiLink> {
+        let arena = Arena::new();
+        let opts = ComrakOptions {
+            extension: ComrakExtensionOptions {
+                table: true,
+                autolink: true,
+                ..ComrakExtensionOptions::default()
+            },
+            ..ComrakOptions::default()
+        };
+        let root = parse_document(&arena, markdown, &opts);
+        extract_wikilinks(root)
+    }
+
+    #[test]
+    fn parses_a_bare_file_reference() {
+        let links = parse("See [[other-note]] for more.");
+        assert_eq!(
+            links,
+            vec![WikiLink {
+                file: "other-note".to_string(),
+                block: None,
+                label: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn parses_a_block_reference_and_label() {
+        let links = parse("[[b#Section Two|see this]]");
+        assert_eq!(
+            links,
+            vec![WikiLink {
+                file: "b".to_string(),
+                block: Some("Section Two".to_string()),
+                label: Some("see this".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn ignores_bracket_pairs_inside_a_code_span() {
+        let links = parse("C++ has the `[[nodiscard]]` attribute.");
+        assert!(links.is_empty());
+    }
+
+    #[test]
+    fn ignores_bracket_pairs_inside_a_fenced_code_block() {
+        let links = parse("```cpp\n[[nodiscard]]\n```\n\nSee [[other-note]].");
+        assert_eq!(
+            links,
+            vec![WikiLink {
+                file: "other-note".to_string(),
+                block: None,
+                label: None,
+            }]
+        );
+    }
+}